@@ -0,0 +1,155 @@
+use crate::{Batch, Error, KeyValueStore};
+use std::convert::TryInto;
+
+/// A single key/value pair yielded by `IntegerStore::iter_from`/`range`.
+pub type IntegerEntry = Result<(u64, Vec<u8>), Error>;
+
+/// A typed view over a `KeyValueStore` keyed by integers, encoding each key
+/// as 8-byte big-endian so numeric order matches the underlying store's
+/// lexicographic byte order — keeping `range`/`prefix` scans over the
+/// wrapped store in ascending numeric key order.
+pub struct IntegerStore<S> {
+    store: S,
+}
+
+impl<S> IntegerStore<S> {
+    pub fn new(store: S) -> Self {
+        IntegerStore { store }
+    }
+}
+
+impl<'a, S: KeyValueStore<'a>> IntegerStore<S> {
+    pub fn get<K: Into<u64>>(&self, key: K) -> Result<Option<Vec<u8>>, Error> {
+        self.store.get(&encode(key.into()))
+    }
+
+    pub fn exists<K: Into<u64>>(&self, key: K) -> Result<bool, Error> {
+        self.store.exists(&encode(key.into()))
+    }
+
+    pub fn put<K: Into<u64>>(&'a self, key: K, value: &[u8]) -> Result<(), Error> {
+        let mut batch = self.store.batch()?;
+        batch.put(&encode(key.into()), value)?;
+        batch.commit()
+    }
+
+    pub fn delete<K: Into<u64>>(&'a self, key: K) -> Result<(), Error> {
+        let mut batch = self.store.batch()?;
+        batch.delete(&encode(key.into()))?;
+        batch.commit()
+    }
+
+    /// Iterate all entries from `start` (inclusive) to the end of the store,
+    /// in ascending numeric key order.
+    pub fn iter_from<K: Into<u64>>(
+        &'a self,
+        start: K,
+    ) -> Box<dyn Iterator<Item = IntegerEntry> + 'a> {
+        Box::new(
+            self.store
+                .iter_from(&encode(start.into()))
+                .map(decode_entry),
+        )
+    }
+
+    /// Iterate all entries whose key starts with `prefix`, in ascending
+    /// numeric key order.
+    pub fn prefix(&'a self, prefix: &[u8]) -> Box<dyn Iterator<Item = IntegerEntry> + 'a> {
+        Box::new(self.store.prefix(prefix).map(decode_entry))
+    }
+
+    /// Iterate all entries with `start <= key < end`, in ascending numeric
+    /// key order.
+    pub fn range<K: Into<u64>>(
+        &'a self,
+        start: K,
+        end: K,
+    ) -> Box<dyn Iterator<Item = IntegerEntry> + 'a> {
+        Box::new(
+            self.store
+                .range(&encode(start.into()), &encode(end.into()))
+                .map(decode_entry),
+        )
+    }
+}
+
+fn encode(key: u64) -> [u8; 8] {
+    key.to_be_bytes()
+}
+
+fn decode_entry(item: Result<(Vec<u8>, Vec<u8>), Error>) -> IntegerEntry {
+    item.map(|(k, v)| {
+        (
+            u64::from_be_bytes(k.try_into().expect("integer key must be 8 bytes")),
+            v,
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::Store as MemoryStore;
+
+    #[test]
+    fn put_and_get() {
+        let store = IntegerStore::new(MemoryStore::new(""));
+        store.put(1u64, &[1]).unwrap();
+        store.put(2u64, &[2]).unwrap();
+
+        assert_eq!(Some(vec![1]), store.get(1u64).unwrap());
+        assert_eq!(Some(vec![2]), store.get(2u64).unwrap());
+        assert_eq!(None, store.get(3u64).unwrap());
+    }
+
+    #[test]
+    fn delete() {
+        let store = IntegerStore::new(MemoryStore::new(""));
+        store.put(1u64, &[1]).unwrap();
+        assert!(store.exists(1u64).unwrap());
+
+        store.delete(1u64).unwrap();
+        assert!(!store.exists(1u64).unwrap());
+    }
+
+    #[test]
+    fn keys_are_ordered_numerically() {
+        let store = IntegerStore::new(MemoryStore::new(""));
+        store.put(256u64, &[2]).unwrap();
+        store.put(1u64, &[0]).unwrap();
+        store.put(2u64, &[1]).unwrap();
+
+        let got: Vec<_> = store
+            .iter_from(0u64)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(vec![(1, vec![0]), (2, vec![1]), (256, vec![2])], got);
+    }
+
+    #[test]
+    fn range_is_ordered_numerically() {
+        let store = IntegerStore::new(MemoryStore::new(""));
+        store.put(256u64, &[2]).unwrap();
+        store.put(1u64, &[0]).unwrap();
+        store.put(2u64, &[1]).unwrap();
+
+        let got: Vec<_> = store
+            .range(1u64, 256u64)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(vec![(1, vec![0]), (2, vec![1])], got);
+    }
+
+    #[test]
+    fn prefix_decodes_keys() {
+        let store = IntegerStore::new(MemoryStore::new(""));
+        store.put(1u64, &[0]).unwrap();
+        store.put(2u64, &[1]).unwrap();
+
+        let got: Vec<_> = store
+            .prefix(&[0, 0, 0, 0, 0, 0, 0])
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(vec![(1, vec![0]), (2, vec![1])], got);
+    }
+}
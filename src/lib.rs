@@ -0,0 +1,71 @@
+mod integer;
+mod memory;
+mod migrate;
+mod rocksdb;
+mod sled;
+
+pub use crate::integer::IntegerStore;
+pub use crate::memory::Store as MemoryStore;
+pub use crate::migrate::migrate;
+pub use crate::rocksdb::Store as RocksdbStore;
+pub use crate::sled::Store as SledStore;
+
+use std::fmt;
+
+/// Errors produced by any `KeyValueStore` backend.
+#[derive(Debug)]
+pub enum Error {
+    DBError(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::DBError(msg) => write!(f, "db error: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// A single key/value pair yielded by `iter_from`/`prefix`/`range`.
+pub type Entry = Result<(Vec<u8>, Vec<u8>), Error>;
+
+/// The boxed iterator type returned by `iter_from`/`prefix`/`range`.
+pub type EntryIter<'a> = Box<dyn Iterator<Item = Entry> + 'a>;
+
+/// A key/value store backend benchmarked by this crate.
+pub trait KeyValueStore<'a>: Sized {
+    type Batch: Batch;
+    type Tree: KeyValueStore<'a>;
+
+    fn new(path: &str) -> Self;
+
+    fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, Error>;
+
+    fn exists(&self, key: &[u8]) -> Result<bool, Error>;
+
+    fn batch(&'a self) -> Result<Self::Batch, Error>;
+
+    /// Open (creating if necessary) an independent, named sub-store living
+    /// alongside this one in the same database.
+    fn open_tree(&self, name: &str) -> Result<Self::Tree, Error>;
+
+    /// Iterate all entries from `start` (inclusive) to the end of the store, in key order.
+    fn iter_from(&'a self, start: &[u8]) -> EntryIter<'a>;
+
+    /// Iterate all entries whose key starts with `prefix`, in key order.
+    fn prefix(&'a self, prefix: &[u8]) -> EntryIter<'a>;
+
+    /// Iterate all entries with `start <= key < end`, in key order.
+    fn range(&'a self, start: &[u8], end: &[u8]) -> EntryIter<'a>;
+}
+
+/// A group of `put`/`delete` operations applied atomically on `commit`.
+pub trait Batch {
+    fn put(&mut self, key: &[u8], value: &[u8]) -> Result<(), Error>;
+
+    fn delete(&mut self, key: &[u8]) -> Result<(), Error>;
+
+    fn commit(self) -> Result<(), Error>;
+}
@@ -0,0 +1,280 @@
+use crate::{Batch as KVBatch, EntryIter, Error, KeyValueStore};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, RwLock};
+
+type Map = Arc<RwLock<BTreeMap<Vec<u8>, Vec<u8>>>>;
+type Trees = Arc<RwLock<HashMap<String, Map>>>;
+
+/// A pure in-memory `KeyValueStore` backed by a `BTreeMap`, useful as a
+/// baseline to isolate a disk backend's syscall/IO cost from pure
+/// data-structure cost. The `path` argument is ignored.
+pub struct Store {
+    map: Map,
+    trees: Trees,
+    is_tree: bool,
+}
+
+impl<'a> KeyValueStore<'a> for Store {
+    type Batch = Batch;
+    type Tree = Store;
+
+    fn new(_path: &str) -> Self {
+        Store {
+            map: Arc::new(RwLock::new(BTreeMap::new())),
+            trees: Arc::new(RwLock::new(HashMap::new())),
+            is_tree: false,
+        }
+    }
+
+    fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, Error> {
+        Ok(self.map.read().unwrap().get(key).cloned())
+    }
+
+    fn exists(&self, key: &[u8]) -> Result<bool, Error> {
+        Ok(self.map.read().unwrap().contains_key(key))
+    }
+
+    fn batch(&self) -> Result<Self::Batch, Error> {
+        Ok(Batch {
+            map: Arc::clone(&self.map),
+            ops: Vec::new(),
+        })
+    }
+
+    fn open_tree(&self, name: &str) -> Result<Self::Tree, Error> {
+        if self.is_tree {
+            return Err(Error::DBError(
+                "cannot open a tree from within another tree".to_string(),
+            ));
+        }
+
+        let map = self
+            .trees
+            .write()
+            .unwrap()
+            .entry(name.to_string())
+            .or_insert_with(|| Arc::new(RwLock::new(BTreeMap::new())))
+            .clone();
+
+        Ok(Store {
+            map,
+            trees: Arc::clone(&self.trees),
+            is_tree: true,
+        })
+    }
+
+    fn iter_from(&'a self, start: &[u8]) -> EntryIter<'a> {
+        let entries: Vec<_> = self
+            .map
+            .read()
+            .unwrap()
+            .range(start.to_vec()..)
+            .map(|(k, v)| Ok((k.clone(), v.clone())))
+            .collect();
+
+        Box::new(entries.into_iter())
+    }
+
+    fn prefix(&'a self, prefix: &[u8]) -> EntryIter<'a> {
+        let prefix = prefix.to_vec();
+        let entries: Vec<_> = self
+            .map
+            .read()
+            .unwrap()
+            .range(prefix.clone()..)
+            .take_while(|(k, _)| k.starts_with(&prefix))
+            .map(|(k, v)| Ok((k.clone(), v.clone())))
+            .collect();
+
+        Box::new(entries.into_iter())
+    }
+
+    fn range(&'a self, start: &[u8], end: &[u8]) -> EntryIter<'a> {
+        let entries: Vec<_> = self
+            .map
+            .read()
+            .unwrap()
+            .range(start.to_vec()..end.to_vec())
+            .map(|(k, v)| Ok((k.clone(), v.clone())))
+            .collect();
+
+        Box::new(entries.into_iter())
+    }
+}
+
+enum Op {
+    Put(Vec<u8>, Vec<u8>),
+    Delete(Vec<u8>),
+}
+
+/// Accumulates `put`/`delete` operations and applies them to the map in a
+/// single locked pass on `commit`.
+pub struct Batch {
+    map: Map,
+    ops: Vec<Op>,
+}
+
+impl KVBatch for Batch {
+    fn put(&mut self, key: &[u8], value: &[u8]) -> Result<(), Error> {
+        self.ops.push(Op::Put(key.to_vec(), value.to_vec()));
+        Ok(())
+    }
+
+    fn delete(&mut self, key: &[u8]) -> Result<(), Error> {
+        self.ops.push(Op::Delete(key.to_vec()));
+        Ok(())
+    }
+
+    fn commit(self) -> Result<(), Error> {
+        let mut map = self.map.write().unwrap();
+        for op in self.ops {
+            match op {
+                Op::Put(k, v) => {
+                    map.insert(k, v);
+                }
+                Op::Delete(k) => {
+                    map.remove(&k);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn put_and_get() {
+        let store = Store::new("");
+        let mut batch = store.batch().unwrap();
+        batch.put(&[0, 0], &[0, 0, 0]).unwrap();
+        batch.put(&[1, 1], &[1, 1, 1]).unwrap();
+        batch.commit().unwrap();
+
+        assert_eq!(Some(vec![0, 0, 0]), store.get(&[0, 0]).unwrap());
+        assert_eq!(Some(vec![1, 1, 1]), store.get(&[1, 1]).unwrap());
+        assert_eq!(None, store.get(&[2, 2]).unwrap())
+    }
+
+    #[test]
+    fn exists() {
+        let store = Store::new("");
+        assert!(!store.exists(&[0, 0]).unwrap());
+
+        let mut batch = store.batch().unwrap();
+        batch.put(&[0, 0], &[0, 0, 0]).unwrap();
+        batch.commit().unwrap();
+
+        assert!(store.exists(&[0, 0]).unwrap());
+    }
+
+    #[test]
+    fn delete() {
+        let store = Store::new("");
+        let mut batch = store.batch().unwrap();
+        batch.put(&[0, 0], &[0, 0, 0]).unwrap();
+        batch.commit().unwrap();
+        assert_eq!(Some(vec![0, 0, 0]), store.get(&[0, 0]).unwrap());
+
+        let mut batch = store.batch().unwrap();
+        batch.delete(&[0, 0]).unwrap();
+        batch.commit().unwrap();
+        assert_eq!(None, store.get(&[0, 0]).unwrap());
+    }
+
+    #[test]
+    fn batch_not_visible_until_commit() {
+        let store = Store::new("");
+
+        let mut batch = store.batch().unwrap();
+        batch.put(&[0, 0], &[0, 0, 0]).unwrap();
+        assert_eq!(None, store.get(&[0, 0]).unwrap());
+
+        batch.commit().unwrap();
+        assert_eq!(Some(vec![0, 0, 0]), store.get(&[0, 0]).unwrap());
+    }
+
+    #[test]
+    fn range_is_ordered() {
+        let store = Store::new("");
+        let mut batch = store.batch().unwrap();
+        batch.put(&[1, 0], &[2]).unwrap();
+        batch.put(&[0, 0], &[0]).unwrap();
+        batch.put(&[0, 1], &[1]).unwrap();
+        batch.commit().unwrap();
+
+        let got: Vec<_> = store
+            .iter_from(&[0, 0])
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(
+            vec![
+                (vec![0, 0], vec![0]),
+                (vec![0, 1], vec![1]),
+                (vec![1, 0], vec![2]),
+            ],
+            got
+        );
+    }
+
+    fn seed(store: &Store) {
+        let mut batch = store.batch().unwrap();
+        batch.put(&[0, 0], &[0]).unwrap();
+        batch.put(&[0, 1], &[1]).unwrap();
+        batch.put(&[1, 0], &[2]).unwrap();
+        batch.put(&[1, 1], &[3]).unwrap();
+        batch.commit().unwrap();
+    }
+
+    #[test]
+    fn prefix() {
+        let store = Store::new("");
+        seed(&store);
+
+        let got: Vec<_> = store.prefix(&[0]).collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(vec![(vec![0, 0], vec![0]), (vec![0, 1], vec![1])], got);
+    }
+
+    #[test]
+    fn range() {
+        let store = Store::new("");
+        seed(&store);
+
+        let got: Vec<_> = store
+            .range(&[0, 1], &[1, 1])
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(vec![(vec![0, 1], vec![1]), (vec![1, 0], vec![2])], got);
+    }
+
+    #[test]
+    fn open_tree_is_isolated() {
+        let store = Store::new("");
+
+        let mut batch = store.batch().unwrap();
+        batch.put(&[0, 0], &[0]).unwrap();
+        batch.commit().unwrap();
+
+        let headers = store.open_tree("headers").unwrap();
+        assert_eq!(None, headers.get(&[0, 0]).unwrap());
+
+        let mut batch = headers.batch().unwrap();
+        batch.put(&[0, 0], &[1]).unwrap();
+        batch.commit().unwrap();
+
+        assert_eq!(Some(vec![0]), store.get(&[0, 0]).unwrap());
+        assert_eq!(Some(vec![1]), headers.get(&[0, 0]).unwrap());
+
+        let headers_again = store.open_tree("headers").unwrap();
+        assert_eq!(Some(vec![1]), headers_again.get(&[0, 0]).unwrap());
+    }
+
+    #[test]
+    fn open_tree_from_tree_errors() {
+        let store = Store::new("");
+        let headers = store.open_tree("headers").unwrap();
+        assert!(headers.open_tree("bodies").is_err());
+    }
+}
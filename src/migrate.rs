@@ -0,0 +1,107 @@
+use crate::{Batch, Error, KeyValueStore};
+
+const BATCH_SIZE: usize = 1024;
+
+/// Stream every key/value pair from `src` into `dst`, committing in
+/// batches, and return the number of entries copied. Backend-agnostic:
+/// `src` and `dst` can be any two (possibly different) `KeyValueStore`
+/// implementations.
+pub fn migrate<'a, S, D>(src: &'a S, dst: &'a D) -> Result<u64, Error>
+where
+    S: KeyValueStore<'a>,
+    D: KeyValueStore<'a>,
+{
+    let mut count = 0u64;
+    let mut pending = 0usize;
+    let mut batch = dst.batch()?;
+
+    for entry in src.iter_from(&[]) {
+        let (key, value) = entry?;
+        batch.put(&key, &value)?;
+        pending += 1;
+        count += 1;
+
+        if pending >= BATCH_SIZE {
+            batch.commit()?;
+            batch = dst.batch()?;
+            pending = 0;
+        }
+    }
+
+    if pending > 0 {
+        batch.commit()?;
+    }
+
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::Store as MemoryStore;
+    use crate::sled::Store as SledStore;
+
+    #[test]
+    fn round_trip_preserves_all_keys() {
+        let src = MemoryStore::new("");
+        let mut batch = src.batch().unwrap();
+        for i in 0u8..10 {
+            batch.put(&[i], &[i, i]).unwrap();
+        }
+        batch.commit().unwrap();
+
+        let dst = MemoryStore::new("");
+        let copied = migrate(&src, &dst).unwrap();
+        assert_eq!(10, copied);
+
+        for i in 0u8..10 {
+            assert_eq!(src.get(&[i]).unwrap(), dst.get(&[i]).unwrap());
+        }
+    }
+
+    #[test]
+    fn round_trip_across_backends() {
+        let tmp_dir = tempfile::Builder::new()
+            .prefix("round_trip_across_backends")
+            .tempdir()
+            .unwrap();
+
+        let src = MemoryStore::new("");
+        let mut batch = src.batch().unwrap();
+        for i in 0u8..10 {
+            batch.put(&[i], &[i, i]).unwrap();
+        }
+        batch.commit().unwrap();
+
+        let dst = SledStore::new(tmp_dir.path().to_str().unwrap());
+        let copied = migrate(&src, &dst).unwrap();
+        assert_eq!(10, copied);
+
+        for i in 0u8..10 {
+            assert_eq!(src.get(&[i]).unwrap(), dst.get(&[i]).unwrap());
+        }
+    }
+
+    #[test]
+    fn round_trip_spans_multiple_batches() {
+        let entries = BATCH_SIZE * 2 + 1;
+
+        let src = MemoryStore::new("");
+        let mut batch = src.batch().unwrap();
+        for i in 0..entries as u32 {
+            batch.put(&i.to_be_bytes(), &i.to_be_bytes()).unwrap();
+        }
+        batch.commit().unwrap();
+
+        let dst = MemoryStore::new("");
+        let copied = migrate(&src, &dst).unwrap();
+        assert_eq!(entries as u64, copied);
+
+        for i in 0..entries as u32 {
+            assert_eq!(
+                src.get(&i.to_be_bytes()).unwrap(),
+                dst.get(&i.to_be_bytes()).unwrap()
+            );
+        }
+    }
+}
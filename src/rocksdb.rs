@@ -0,0 +1,404 @@
+use crate::{Batch as KVBatch, EntryIter, Error, KeyValueStore};
+use rocksdb::{
+    ColumnFamilyDescriptor, ColumnFamilyRef, DBWithThreadMode, IteratorMode, MultiThreaded,
+    Options, WriteBatch, WriteOptions,
+};
+use std::sync::Arc;
+
+/// `create_cf` needs to be callable through a shared `Arc<DB>` (trees
+/// are opened from `&self`, and the handle is cloned into every `Batch` and
+/// `Store::Tree`), so this backend requires the `multi-threaded-cf` feature,
+/// whose `DBWithThreadMode<MultiThreaded>` guards column family bookkeeping
+/// internally and exposes `create_cf` via `&self` instead of `&mut self`.
+type Db = DBWithThreadMode<MultiThreaded>;
+
+pub struct Store {
+    db: Arc<Db>,
+    cf: Option<String>,
+}
+
+impl Store {
+    fn cf(&self) -> Option<ColumnFamilyRef> {
+        self.cf
+            .as_ref()
+            .map(|name| self.db.cf_handle(name).expect("missing column family"))
+    }
+}
+
+impl<'a> KeyValueStore<'a> for Store {
+    type Batch = Batch;
+    type Tree = Store;
+
+    fn new(path: &str) -> Self {
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+        opts.create_missing_column_families(true);
+
+        // A DB that was previously opened with named trees refuses to reopen
+        // unless every existing column family is listed up front, so list
+        // whatever is already on disk (a fresh DB has none but "default").
+        let cf_names = Db::list_cf(&opts, path).unwrap_or_else(|_| vec!["default".to_string()]);
+        let cfs = cf_names
+            .iter()
+            .map(|name| ColumnFamilyDescriptor::new(name, Options::default()));
+
+        let db = Db::open_cf_descriptors(&opts, path, cfs).expect("Failed to open rocksdb");
+
+        Store {
+            db: Arc::new(db),
+            cf: None,
+        }
+    }
+
+    fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, Error> {
+        let val = match self.cf() {
+            Some(cf) => self.db.get_cf(&cf, key)?,
+            None => self.db.get(key)?,
+        }
+        .map(|v| v.to_vec());
+
+        Ok(val)
+    }
+
+    fn exists(&self, key: &[u8]) -> Result<bool, Error> {
+        let val = match self.cf() {
+            Some(cf) => self.db.get_cf(&cf, key)?,
+            None => self.db.get(key)?,
+        };
+
+        Ok(val.is_some())
+    }
+
+    fn batch(&self) -> Result<Self::Batch, Error> {
+        Ok(Batch {
+            db: Arc::clone(&self.db),
+            cf: self.cf.clone(),
+            ops: WriteBatch::default(),
+        })
+    }
+
+    fn open_tree(&self, name: &str) -> Result<Self::Tree, Error> {
+        if self.cf.is_some() {
+            return Err(Error::DBError(
+                "cannot open a tree from within another tree".to_string(),
+            ));
+        }
+
+        if self.db.cf_handle(name).is_none() {
+            self.db.create_cf(name, &Options::default())?;
+        }
+
+        Ok(Store {
+            db: Arc::clone(&self.db),
+            cf: Some(name.to_string()),
+        })
+    }
+
+    fn iter_from(&'a self, start: &[u8]) -> EntryIter<'a> {
+        let mode = IteratorMode::From(start, rocksdb::Direction::Forward);
+        let iter = match self.cf() {
+            Some(cf) => self.db.iterator_cf(&cf, mode),
+            None => self.db.iterator(mode),
+        };
+
+        Box::new(
+            iter.map(|item| item.map(|(k, v)| (k.to_vec(), v.to_vec())).map_err(Into::into)),
+        )
+    }
+
+    fn prefix(&'a self, prefix: &[u8]) -> EntryIter<'a> {
+        let prefix = prefix.to_vec();
+        let mode = IteratorMode::From(&prefix, rocksdb::Direction::Forward);
+        let iter = match self.cf() {
+            Some(cf) => self.db.iterator_cf(&cf, mode),
+            None => self.db.iterator(mode),
+        };
+
+        Box::new(
+            iter.take_while(move |item| match item {
+                Ok((k, _)) => k.starts_with(&prefix),
+                Err(_) => true,
+            })
+            .map(|item| item.map(|(k, v)| (k.to_vec(), v.to_vec())).map_err(Into::into)),
+        )
+    }
+
+    fn range(&'a self, start: &[u8], end: &[u8]) -> EntryIter<'a> {
+        let end = end.to_vec();
+        let mode = IteratorMode::From(start, rocksdb::Direction::Forward);
+        let iter = match self.cf() {
+            Some(cf) => self.db.iterator_cf(&cf, mode),
+            None => self.db.iterator(mode),
+        };
+
+        Box::new(
+            iter.take_while(move |item| match item {
+                Ok((k, _)) => k.as_ref() < end.as_slice(),
+                Err(_) => true,
+            })
+            .map(|item| item.map(|(k, v)| (k.to_vec(), v.to_vec())).map_err(Into::into)),
+        )
+    }
+}
+
+/// Accumulates `put`/`delete` operations and applies them to the database
+/// as a single atomic `rocksdb::WriteBatch` on `commit`, or `commit_sync`
+/// for a write that's fsynced to disk before returning.
+pub struct Batch {
+    db: Arc<Db>,
+    cf: Option<String>,
+    ops: WriteBatch,
+}
+
+impl KVBatch for Batch {
+    fn put(&mut self, key: &[u8], value: &[u8]) -> Result<(), Error> {
+        match &self.cf {
+            Some(name) => {
+                let cf = self.db.cf_handle(name).expect("missing column family");
+                self.ops.put_cf(&cf, key, value);
+            }
+            None => self.ops.put(key, value),
+        }
+        Ok(())
+    }
+
+    fn delete(&mut self, key: &[u8]) -> Result<(), Error> {
+        match &self.cf {
+            Some(name) => {
+                let cf = self.db.cf_handle(name).expect("missing column family");
+                self.ops.delete_cf(&cf, key);
+            }
+            None => self.ops.delete(key),
+        }
+        Ok(())
+    }
+
+    fn commit(self) -> Result<(), Error> {
+        self.db.write(self.ops)?;
+        Ok(())
+    }
+}
+
+impl Batch {
+    /// Like `commit`, but fsyncs the write to disk before returning.
+    pub fn commit_sync(self) -> Result<(), Error> {
+        let mut write_opts = WriteOptions::default();
+        write_opts.set_sync(true);
+
+        self.db.write_opt(self.ops, &write_opts)?;
+        Ok(())
+    }
+}
+
+impl From<rocksdb::Error> for Error {
+    fn from(e: rocksdb::Error) -> Error {
+        Error::DBError(e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn put_and_get() {
+        let tmp_dir = tempfile::Builder::new()
+            .prefix("put_and_get")
+            .tempdir()
+            .unwrap();
+
+        let store = Store::new(tmp_dir.path().to_str().unwrap());
+        let mut batch = store.batch().unwrap();
+        batch.put(&[0, 0], &[0, 0, 0]).unwrap();
+        batch.put(&[1, 1], &[1, 1, 1]).unwrap();
+        batch.commit().unwrap();
+
+        assert_eq!(Some(vec![0, 0, 0]), store.get(&[0, 0]).unwrap());
+        assert_eq!(Some(vec![1, 1, 1]), store.get(&[1, 1]).unwrap());
+        assert_eq!(None, store.get(&[2, 2]).unwrap())
+    }
+
+    #[test]
+    fn exists() {
+        let tmp_dir = tempfile::Builder::new().prefix("exists").tempdir().unwrap();
+        let store = Store::new(tmp_dir.path().to_str().unwrap());
+        assert!(!store.exists(&[0, 0]).unwrap());
+
+        let mut batch = store.batch().unwrap();
+        batch.put(&[0, 0], &[0, 0, 0]).unwrap();
+        batch.commit().unwrap();
+
+        assert!(store.exists(&[0, 0]).unwrap());
+    }
+
+    #[test]
+    fn delete() {
+        let tmp_dir = tempfile::Builder::new().prefix("delete").tempdir().unwrap();
+        let store = Store::new(tmp_dir.path().to_str().unwrap());
+        let mut batch = store.batch().unwrap();
+        batch.put(&[0, 0], &[0, 0, 0]).unwrap();
+        batch.commit().unwrap();
+        assert_eq!(Some(vec![0, 0, 0]), store.get(&[0, 0]).unwrap());
+
+        let mut batch = store.batch().unwrap();
+        batch.delete(&[0, 0]).unwrap();
+        batch.commit().unwrap();
+        assert_eq!(None, store.get(&[0, 0]).unwrap());
+    }
+
+    #[test]
+    fn batch_not_visible_until_commit() {
+        let tmp_dir = tempfile::Builder::new()
+            .prefix("batch_not_visible_until_commit")
+            .tempdir()
+            .unwrap();
+        let store = Store::new(tmp_dir.path().to_str().unwrap());
+
+        let mut batch = store.batch().unwrap();
+        batch.put(&[0, 0], &[0, 0, 0]).unwrap();
+        assert_eq!(None, store.get(&[0, 0]).unwrap());
+
+        batch.commit().unwrap();
+        assert_eq!(Some(vec![0, 0, 0]), store.get(&[0, 0]).unwrap());
+    }
+
+    #[test]
+    fn commit_sync_applies_the_batch() {
+        let tmp_dir = tempfile::Builder::new()
+            .prefix("commit_sync_applies_the_batch")
+            .tempdir()
+            .unwrap();
+        let store = Store::new(tmp_dir.path().to_str().unwrap());
+
+        let mut batch = store.batch().unwrap();
+        batch.put(&[0, 0], &[0, 0, 0]).unwrap();
+        assert_eq!(None, store.get(&[0, 0]).unwrap());
+
+        batch.commit_sync().unwrap();
+        assert_eq!(Some(vec![0, 0, 0]), store.get(&[0, 0]).unwrap());
+    }
+
+    #[test]
+    fn open_tree_is_isolated() {
+        let tmp_dir = tempfile::Builder::new()
+            .prefix("open_tree_is_isolated")
+            .tempdir()
+            .unwrap();
+        let store = Store::new(tmp_dir.path().to_str().unwrap());
+
+        let mut batch = store.batch().unwrap();
+        batch.put(&[0, 0], &[0]).unwrap();
+        batch.commit().unwrap();
+
+        let headers = store.open_tree("headers").unwrap();
+        assert_eq!(None, headers.get(&[0, 0]).unwrap());
+
+        let mut batch = headers.batch().unwrap();
+        batch.put(&[0, 0], &[1]).unwrap();
+        batch.commit().unwrap();
+
+        assert_eq!(Some(vec![0]), store.get(&[0, 0]).unwrap());
+        assert_eq!(Some(vec![1]), headers.get(&[0, 0]).unwrap());
+    }
+
+    #[test]
+    fn reopening_a_db_with_existing_trees() {
+        let tmp_dir = tempfile::Builder::new()
+            .prefix("reopening_a_db_with_existing_trees")
+            .tempdir()
+            .unwrap();
+        let path = tmp_dir.path().to_str().unwrap();
+
+        {
+            let store = Store::new(path);
+            let headers = store.open_tree("headers").unwrap();
+            let mut batch = headers.batch().unwrap();
+            batch.put(&[0, 0], &[1]).unwrap();
+            batch.commit().unwrap();
+        }
+
+        let store = Store::new(path);
+        let headers = store.open_tree("headers").unwrap();
+        assert_eq!(Some(vec![1]), headers.get(&[0, 0]).unwrap());
+    }
+
+    fn seed(store: &Store) {
+        let mut batch = store.batch().unwrap();
+        batch.put(&[0, 0], &[0]).unwrap();
+        batch.put(&[0, 1], &[1]).unwrap();
+        batch.put(&[1, 0], &[2]).unwrap();
+        batch.put(&[1, 1], &[3]).unwrap();
+        batch.commit().unwrap();
+    }
+
+    #[test]
+    fn iter_from() {
+        let tmp_dir = tempfile::Builder::new()
+            .prefix("iter_from")
+            .tempdir()
+            .unwrap();
+        let store = Store::new(tmp_dir.path().to_str().unwrap());
+        seed(&store);
+
+        let got: Vec<_> = store
+            .iter_from(&[0, 1])
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(
+            vec![
+                (vec![0, 1], vec![1]),
+                (vec![1, 0], vec![2]),
+                (vec![1, 1], vec![3]),
+            ],
+            got
+        );
+    }
+
+    #[test]
+    fn prefix() {
+        let tmp_dir = tempfile::Builder::new().prefix("prefix").tempdir().unwrap();
+        let store = Store::new(tmp_dir.path().to_str().unwrap());
+        seed(&store);
+
+        let got: Vec<_> = store.prefix(&[0]).collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(vec![(vec![0, 0], vec![0]), (vec![0, 1], vec![1])], got);
+    }
+
+    #[test]
+    fn range() {
+        let tmp_dir = tempfile::Builder::new().prefix("range").tempdir().unwrap();
+        let store = Store::new(tmp_dir.path().to_str().unwrap());
+        seed(&store);
+
+        let got: Vec<_> = store
+            .range(&[0, 1], &[1, 1])
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(vec![(vec![0, 1], vec![1]), (vec![1, 0], vec![2])], got);
+    }
+
+    #[test]
+    fn iter_from_through_open_tree() {
+        let tmp_dir = tempfile::Builder::new()
+            .prefix("iter_from_through_open_tree")
+            .tempdir()
+            .unwrap();
+        let store = Store::new(tmp_dir.path().to_str().unwrap());
+        let headers = store.open_tree("headers").unwrap();
+        seed(&headers);
+
+        let got: Vec<_> = headers
+            .iter_from(&[0, 1])
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(
+            vec![
+                (vec![0, 1], vec![1]),
+                (vec![1, 0], vec![2]),
+                (vec![1, 1], vec![3]),
+            ],
+            got
+        );
+        assert!(store.iter_from(&[]).collect::<Result<Vec<_>, _>>().unwrap().is_empty());
+    }
+}
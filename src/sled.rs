@@ -1,51 +1,144 @@
-use crate::{Batch, Error, KeyValueStore};
+use crate::{Batch as KVBatch, EntryIter, Error, KeyValueStore};
 use sled::Db;
 use std::sync::Arc;
 
+/// The backing handle a `Store` operates on: either the database's default
+/// tree, or one of its named trees opened via `open_tree`.
+#[derive(Clone)]
+enum Inner {
+    Db(Arc<Db>),
+    Tree(Arc<sled::Tree>),
+}
+
+impl Inner {
+    fn get(&self, key: &[u8]) -> Result<Option<sled::IVec>, sled::Error> {
+        match self {
+            Inner::Db(db) => db.get(key),
+            Inner::Tree(tree) => tree.get(key),
+        }
+    }
+
+    fn contains_key(&self, key: &[u8]) -> Result<bool, sled::Error> {
+        match self {
+            Inner::Db(db) => db.contains_key(key),
+            Inner::Tree(tree) => tree.contains_key(key),
+        }
+    }
+
+    fn apply_batch(&self, batch: sled::Batch) -> Result<(), sled::Error> {
+        match self {
+            Inner::Db(db) => db.apply_batch(batch),
+            Inner::Tree(tree) => tree.apply_batch(batch),
+        }
+    }
+
+    fn range<R: std::ops::RangeBounds<Vec<u8>>>(&self, range: R) -> sled::Iter {
+        match self {
+            Inner::Db(db) => db.range(range),
+            Inner::Tree(tree) => tree.range(range),
+        }
+    }
+
+    fn scan_prefix(&self, prefix: &[u8]) -> sled::Iter {
+        match self {
+            Inner::Db(db) => db.scan_prefix(prefix),
+            Inner::Tree(tree) => tree.scan_prefix(prefix),
+        }
+    }
+}
+
 pub struct Store {
-    db: Arc<Db>,
+    inner: Inner,
 }
 
 impl<'a> KeyValueStore<'a> for Store {
-    type Batch = Store;
+    type Batch = Batch;
+    type Tree = Store;
 
     fn new(path: &str) -> Self {
-        let db = Db::start_default(path).expect("Failed to open sled");
-        let db = Arc::new(db);
+        let db = sled::open(path).expect("Failed to open sled");
 
-        Store { db }
+        Store {
+            inner: Inner::Db(Arc::new(db)),
+        }
     }
 
     fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, Error> {
-        let val = self.db.get(key)?.map(|v| v.to_vec());
+        let val = self.inner.get(key)?.map(|v| v.to_vec());
 
         Ok(val)
     }
 
     fn exists(&self, key: &[u8]) -> Result<bool, Error> {
-        self.db.contains_key(key).map_err(Into::into)
+        self.inner.contains_key(key).map_err(Into::into)
     }
 
     fn batch(&self) -> Result<Self::Batch, Error> {
-        Ok(Self::Batch {
-            db: Arc::clone(&self.db),
+        Ok(Batch {
+            inner: self.inner.clone(),
+            ops: sled::Batch::default(),
         })
     }
+
+    fn open_tree(&self, name: &str) -> Result<Self::Tree, Error> {
+        match &self.inner {
+            Inner::Db(db) => {
+                let tree = db.open_tree(name.as_bytes())?;
+                Ok(Store {
+                    inner: Inner::Tree(Arc::new(tree)),
+                })
+            }
+            Inner::Tree(_) => Err(Error::DBError(
+                "cannot open a tree from within another tree".to_string(),
+            )),
+        }
+    }
+
+    fn iter_from(&'a self, start: &[u8]) -> EntryIter<'a> {
+        Box::new(
+            self.inner
+                .range(start.to_vec()..)
+                .map(|item| item.map(|(k, v)| (k.to_vec(), v.to_vec())).map_err(Into::into)),
+        )
+    }
+
+    fn prefix(&'a self, prefix: &[u8]) -> EntryIter<'a> {
+        Box::new(
+            self.inner
+                .scan_prefix(prefix)
+                .map(|item| item.map(|(k, v)| (k.to_vec(), v.to_vec())).map_err(Into::into)),
+        )
+    }
+
+    fn range(&'a self, start: &[u8], end: &[u8]) -> EntryIter<'a> {
+        Box::new(
+            self.inner
+                .range(start.to_vec()..end.to_vec())
+                .map(|item| item.map(|(k, v)| (k.to_vec(), v.to_vec())).map_err(Into::into)),
+        )
+    }
+}
+
+/// Accumulates `put`/`delete` operations and applies them to the database
+/// as a single atomic `sled::Batch` on `commit`.
+pub struct Batch {
+    inner: Inner,
+    ops: sled::Batch,
 }
 
-impl Batch for Store {
+impl KVBatch for Batch {
     fn put(&mut self, key: &[u8], value: &[u8]) -> Result<(), Error> {
-        self.db.set(key, value)?;
+        self.ops.insert(key, value);
         Ok(())
     }
 
     fn delete(&mut self, key: &[u8]) -> Result<(), Error> {
-        self.db.del(key)?;
+        self.ops.remove(key);
         Ok(())
     }
 
     fn commit(self) -> Result<(), Error> {
-        self.db.flush()?;
+        self.inner.apply_batch(self.ops)?;
         Ok(())
     }
 }
@@ -59,7 +152,6 @@ impl From<sled::Error> for Error {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use tempfile;
 
     #[test]
     fn put_and_get() {
@@ -106,4 +198,104 @@ mod tests {
         batch.commit().unwrap();
         assert_eq!(None, store.get(&[0, 0]).unwrap());
     }
+
+    #[test]
+    fn batch_not_visible_until_commit() {
+        let tmp_dir = tempfile::Builder::new()
+            .prefix("batch_not_visible_until_commit")
+            .tempdir()
+            .unwrap();
+        let store = Store::new(tmp_dir.path().to_str().unwrap());
+
+        let mut batch = store.batch().unwrap();
+        batch.put(&[0, 0], &[0, 0, 0]).unwrap();
+        batch.put(&[1, 1], &[1, 1, 1]).unwrap();
+        assert_eq!(None, store.get(&[0, 0]).unwrap());
+        assert_eq!(None, store.get(&[1, 1]).unwrap());
+
+        batch.commit().unwrap();
+        assert_eq!(Some(vec![0, 0, 0]), store.get(&[0, 0]).unwrap());
+        assert_eq!(Some(vec![1, 1, 1]), store.get(&[1, 1]).unwrap());
+    }
+
+    fn seed(store: &Store) {
+        let mut batch = store.batch().unwrap();
+        batch.put(&[0, 0], &[0]).unwrap();
+        batch.put(&[0, 1], &[1]).unwrap();
+        batch.put(&[1, 0], &[2]).unwrap();
+        batch.put(&[1, 1], &[3]).unwrap();
+        batch.commit().unwrap();
+    }
+
+    #[test]
+    fn iter_from() {
+        let tmp_dir = tempfile::Builder::new()
+            .prefix("iter_from")
+            .tempdir()
+            .unwrap();
+        let store = Store::new(tmp_dir.path().to_str().unwrap());
+        seed(&store);
+
+        let got: Vec<_> = store
+            .iter_from(&[0, 1])
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(
+            vec![
+                (vec![0, 1], vec![1]),
+                (vec![1, 0], vec![2]),
+                (vec![1, 1], vec![3]),
+            ],
+            got
+        );
+    }
+
+    #[test]
+    fn prefix() {
+        let tmp_dir = tempfile::Builder::new().prefix("prefix").tempdir().unwrap();
+        let store = Store::new(tmp_dir.path().to_str().unwrap());
+        seed(&store);
+
+        let got: Vec<_> = store
+            .prefix(&[0])
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(vec![(vec![0, 0], vec![0]), (vec![0, 1], vec![1])], got);
+    }
+
+    #[test]
+    fn range() {
+        let tmp_dir = tempfile::Builder::new().prefix("range").tempdir().unwrap();
+        let store = Store::new(tmp_dir.path().to_str().unwrap());
+        seed(&store);
+
+        let got: Vec<_> = store
+            .range(&[0, 1], &[1, 1])
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(vec![(vec![0, 1], vec![1]), (vec![1, 0], vec![2])], got);
+    }
+
+    #[test]
+    fn open_tree_is_isolated() {
+        let tmp_dir = tempfile::Builder::new()
+            .prefix("open_tree_is_isolated")
+            .tempdir()
+            .unwrap();
+        let store = Store::new(tmp_dir.path().to_str().unwrap());
+
+        let mut batch = store.batch().unwrap();
+        batch.put(&[0, 0], &[0]).unwrap();
+        batch.commit().unwrap();
+
+        let headers = store.open_tree("headers").unwrap();
+        assert_eq!(None, headers.get(&[0, 0]).unwrap());
+
+        let mut batch = headers.batch().unwrap();
+        batch.put(&[0, 0], &[1]).unwrap();
+        batch.commit().unwrap();
+
+        assert_eq!(Some(vec![0]), store.get(&[0, 0]).unwrap());
+        assert_eq!(Some(vec![1]), headers.get(&[0, 0]).unwrap());
+    }
 }